@@ -1,5 +1,7 @@
+use crate::repository::NoteRepository;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
+use std::sync::Arc;
 use tauri::State;
 
 #[derive(Serialize, FromRow)]
@@ -19,6 +21,46 @@ pub struct NoteDetail {
     pub pb_id: Option<String>,
 }
 
+#[derive(Serialize, FromRow)]
+pub struct NoteSearchResult {
+    pub id: i64,
+    pub title: String,
+    pub updated_at: String,
+    pub pb_id: Option<String>,
+    pub excerpt: String,
+}
+
+#[derive(Serialize, FromRow)]
+pub struct NoteRevision {
+    pub id: i64,
+    pub note_id: i64,
+    pub title: String,
+    pub saved_at: String,
+}
+
+// The single row name under which the app's preferences blob is stored
+// in the `config` table.
+const MAIN_CONFIG_NAME: &str = "main";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AppConfig {
+    pub theme: String,
+    pub default_sort_order: String,
+    pub sync_interval_secs: u32,
+    pub last_opened_note_id: Option<i64>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            theme: "system".to_string(),
+            default_sort_order: "updated_at_desc".to_string(),
+            sync_interval_secs: 300,
+            last_opened_note_id: None,
+        }
+    }
+}
+
 #[tauri::command]
 pub fn greet(name: &str) -> String {
     format!("Welcome to ONYX, Operator {}!", name)
@@ -26,29 +68,17 @@ pub fn greet(name: &str) -> String {
 
 #[tauri::command]
 pub async fn create_note(
-    pool: State<'_, SqlitePool>,
+    repo: State<'_, Arc<dyn NoteRepository>>,
     title: String,
     content: String,
 ) -> Result<i64, String> {
-    let result = sqlx::query("INSERT INTO notes (title, content) VALUES ($1, $2)")
-        .bind(title)
-        .bind(content)
-        .execute(&*pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(result.last_insert_rowid())
+    repo.create(title, content).await
 }
 
 #[tauri::command]
-pub async fn get_notes(pool: State<'_, SqlitePool>) -> Result<Vec<Note>, String> {
+pub async fn get_notes(repo: State<'_, Arc<dyn NoteRepository>>) -> Result<Vec<Note>, String> {
     println!("Backend: get_notes called");
-    let notes = sqlx::query_as::<_, Note>(
-        "SELECT id, title, updated_at, pb_id FROM notes ORDER BY updated_at DESC, id DESC",
-    )
-    .fetch_all(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    let notes = repo.list().await?;
     println!("Backend: Found {} notes", notes.len());
     Ok(notes)
 }
@@ -56,95 +86,148 @@ pub async fn get_notes(pool: State<'_, SqlitePool>) -> Result<Vec<Note>, String>
 #[tauri::command]
 pub async fn get_note_content(
     id: i64,
-    pool: State<'_, SqlitePool>,
+    repo: State<'_, Arc<dyn NoteRepository>>,
 ) -> Result<Option<NoteDetail>, String> {
-    let note = sqlx::query_as::<_, NoteDetail>(
-        "SELECT id, title, content, updated_at, pb_id FROM notes WHERE id = $1",
-    )
-    .bind(id)
-    .fetch_optional(&*pool)
-    .await
-    .map_err(|e| e.to_string())?;
-    Ok(note)
+    repo.get_content(id).await
 }
 
 #[tauri::command]
 pub async fn update_note(
-    pool: State<'_, SqlitePool>,
+    repo: State<'_, Arc<dyn NoteRepository>>,
     id: i64,
     title: String,
     content: String,
 ) -> Result<(), String> {
-    sqlx::query("UPDATE notes SET title = $1, content = $2 WHERE id = $3")
-        .bind(title)
-        .bind(content)
-        .bind(id)
-        .execute(&*pool)
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(())
+    repo.update(id, title, content).await
 }
 
 #[tauri::command]
 pub async fn update_note_pb_id(
-    pool: State<'_, SqlitePool>,
+    repo: State<'_, Arc<dyn NoteRepository>>,
     id: i64,
     pb_id: String,
 ) -> Result<(), String> {
     println!("Backend: update_note_pb_id: id={} pb_id={}", id, pb_id);
-    sqlx::query("UPDATE notes SET pb_id = $1 WHERE id = $2")
-        .bind(pb_id)
-        .bind(id)
-        .execute(&*pool)
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(())
+    repo.set_pb_id(id, pb_id).await
 }
 
 #[tauri::command]
 pub async fn import_note_from_pb(
-    pool: State<'_, SqlitePool>,
+    repo: State<'_, Arc<dyn NoteRepository>>,
     pb_id: String,
     title: String,
     content: String,
     updated_at: String,
 ) -> Result<i64, String> {
     println!("Backend: import_note_from_pb: {}", title);
+    repo.import(pb_id, title, content, updated_at).await
+}
+
+#[tauri::command]
+pub async fn delete_note(repo: State<'_, Arc<dyn NoteRepository>>, id: i64) -> Result<(), String> {
+    repo.delete(id).await
+}
 
-    let result = sqlx::query(
-        "INSERT INTO notes (title, content, updated_at, pb_id) VALUES ($1, $2, $3, $4)",
+#[tauri::command]
+pub async fn search_notes(
+    pool: State<'_, SqlitePool>,
+    query: String,
+) -> Result<Vec<NoteSearchResult>, String> {
+    let results = sqlx::query_as::<_, NoteSearchResult>(
+        "SELECT notes.id, notes.title, notes.updated_at, notes.pb_id,
+                snippet(notes_fts, 1, '<mark>', '</mark>', '...', 10) AS excerpt
+         FROM notes_fts
+         JOIN notes ON notes.id = notes_fts.rowid
+         WHERE notes_fts MATCH $1
+         ORDER BY rank",
     )
-    .bind(title)
-    .bind(content)
-    .bind(updated_at)
-    .bind(pb_id)
-    .execute(&*pool)
+    .bind(query)
+    .fetch_all(&*pool)
     .await
     .map_err(|e| e.to_string())?;
 
-    Ok(result.last_insert_rowid())
+    Ok(results)
+}
+
+#[tauri::command]
+pub async fn get_note_revisions(
+    pool: State<'_, SqlitePool>,
+    note_id: i64,
+) -> Result<Vec<NoteRevision>, String> {
+    sqlx::query_as::<_, NoteRevision>(
+        "SELECT id, note_id, title, saved_at FROM note_revisions
+         WHERE note_id = $1
+         ORDER BY id DESC",
+    )
+    .bind(note_id)
+    .fetch_all(&*pool)
+    .await
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn delete_note(pool: State<'_, SqlitePool>, id: i64) -> Result<(), String> {
-    sqlx::query("DELETE FROM notes WHERE id = $1")
-        .bind(id)
+pub async fn restore_note_revision(
+    pool: State<'_, SqlitePool>,
+    revision_id: i64,
+) -> Result<(), String> {
+    let revision: (i64, String, Option<String>) = sqlx::query_as(
+        "SELECT note_id, title, content FROM note_revisions WHERE id = $1",
+    )
+    .bind(revision_id)
+    .fetch_optional(&*pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("revision {} not found", revision_id))?;
+
+    let (note_id, title, content) = revision;
+
+    sqlx::query("UPDATE notes SET title = $1, content = $2 WHERE id = $3")
+        .bind(title)
+        .bind(content)
+        .bind(note_id)
         .execute(&*pool)
         .await
         .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_config(pool: State<'_, SqlitePool>) -> Result<AppConfig, String> {
+    let data: Option<String> = sqlx::query_scalar("SELECT data FROM config WHERE name = $1")
+        .bind(MAIN_CONFIG_NAME)
+        .fetch_optional(&*pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match data {
+        Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string()),
+        None => Ok(AppConfig::default()),
+    }
+}
+
+#[tauri::command]
+pub async fn save_config(pool: State<'_, SqlitePool>, config: AppConfig) -> Result<(), String> {
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+
+    sqlx::query(
+        "INSERT INTO config (name, data) VALUES ($1, $2)
+         ON CONFLICT(name) DO UPDATE SET data = excluded.data",
+    )
+    .bind(MAIN_CONFIG_NAME)
+    .bind(json)
+    .execute(&*pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
     Ok(())
 }
 
 #[tauri::command]
 pub async fn delete_note_by_pb_id(
-    pool: State<'_, SqlitePool>,
+    repo: State<'_, Arc<dyn NoteRepository>>,
     pb_id: String,
 ) -> Result<(), String> {
     println!("Backend: delete_note_by_pb_id: {}", pb_id);
-    sqlx::query("DELETE FROM notes WHERE pb_id = $1")
-        .bind(pb_id)
-        .execute(&*pool)
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(())
+    repo.delete_by_pb_id(pb_id).await
 }
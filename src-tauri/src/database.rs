@@ -1,11 +1,22 @@
-use sqlx::{migrate::MigrateDatabase, Sqlite, SqlitePool};
+use crate::migrations;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::SqlitePool;
 use std::fs;
+use std::str::FromStr;
+use std::time::Duration;
 use tauri::Manager;
 
 // CONSTANTS:
 // The name of our database file.
 const DB_NAME: &str = "onyx.db";
 
+// Connection retry tuning: base delay for the first retry, doubled on
+// each subsequent attempt and capped so a persistently locked database
+// still fails fast instead of hanging the app.
+const CONNECT_BASE_DELAY: Duration = Duration::from_millis(100);
+const CONNECT_MAX_DELAY: Duration = Duration::from_secs(4);
+const CONNECT_MAX_ATTEMPTS: u32 = 5;
+
 // 1. THE BLUEPRINT
 pub struct Database;
 
@@ -25,71 +36,90 @@ impl Database {
         path.to_str().unwrap().to_string()
     }
 
-    pub async fn setup(app_handle: &tauri::AppHandle) -> SqlitePool {
-        let path = Self::get_db_path(app_handle).await;
-        let db_url = format!("sqlite:{}", path);
+    /// Connects to the database, creating the file if it doesn't exist.
+    /// Retries with exponential backoff on failure, since a transient
+    /// lock or a slow filesystem on cold start shouldn't be fatal.
+    async fn connect_with_retry(db_url: &str) -> Result<SqlitePool, String> {
+        let options = SqliteConnectOptions::from_str(db_url)
+            .map_err(|e| format!("invalid database url: {}", e))?
+            .create_if_missing(true);
+
+        let mut delay = CONNECT_BASE_DELAY;
+        let mut last_err = None;
 
-        if !Sqlite::database_exists(&db_url).await.unwrap_or(false) {
-            Sqlite::create_database(&db_url).await.unwrap();
+        for attempt in 1..=CONNECT_MAX_ATTEMPTS {
+            match SqlitePool::connect_with(options.clone()).await {
+                Ok(pool) => return Ok(pool),
+                Err(e) => {
+                    eprintln!(
+                        "Database connect attempt {}/{} failed: {}",
+                        attempt, CONNECT_MAX_ATTEMPTS, e
+                    );
+                    last_err = Some(e);
+                    if attempt < CONNECT_MAX_ATTEMPTS {
+                        tokio::time::sleep(delay).await;
+                        delay = (delay * 2).min(CONNECT_MAX_DELAY);
+                    }
+                }
+            }
         }
 
-        let pool = SqlitePool::connect(&db_url).await.unwrap();
+        Err(format!(
+            "failed to connect to database after {} attempts: {}",
+            CONNECT_MAX_ATTEMPTS,
+            last_err.map(|e| e.to_string()).unwrap_or_default()
+        ))
+    }
+
+    pub async fn setup(app_handle: &tauri::AppHandle) -> Result<SqlitePool, String> {
+        let path = Self::get_db_path(app_handle).await;
+        let db_url = format!("sqlite:{}", path);
+
+        let pool = Self::connect_with_retry(&db_url).await?;
 
         // WAL Mode
         sqlx::query("PRAGMA journal_mode=WAL;")
             .execute(&pool)
             .await
-            .unwrap();
+            .map_err(|e| e.to_string())?;
 
         // Table Creation
         println!("Checking 'notes' table...");
-        if let Err(e) = sqlx::query(
+        sqlx::query(
             "CREATE TABLE IF NOT EXISTS notes (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 title TEXT NOT NULL,
                 content TEXT,
                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                pb_id TEXT
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
             )",
         )
         .execute(&pool)
         .await
-        {
-            eprintln!("CRITICAL ERROR: Failed to create tables: {}", e);
-            panic!("Database setup failed: {}", e);
-        }
+        .map_err(|e| format!("failed to create tables: {}", e))?;
 
-        // Migration: Add pb_id if missing (for existing users)
-        println!("Checking migration for pb_id...");
-        let has_pb_id: bool = sqlx::query_scalar(
-            "SELECT count(*) FROM pragma_table_info('notes') WHERE name='pb_id'",
+        // Migration bookkeeping
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                version INTEGER PRIMARY KEY,
+                applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
         )
-        .fetch_one(&pool)
+        .execute(&pool)
         .await
-        .unwrap_or(0)
-            > 0;
-
-        if !has_pb_id {
-            println!("Applying migration: Adding pb_id column...");
-            match sqlx::query("ALTER TABLE notes ADD COLUMN pb_id TEXT")
-                .execute(&pool)
-                .await
-            {
-                Ok(_) => println!("Migration successful: pb_id added."),
-                Err(e) => {
-                    // Start assuming it might have failed because it exists (race condition?), check again or log warn
-                    eprintln!("Migration failed: {}", e);
-                    // Check if it exists now?
-                }
-            }
-        } else {
-            println!("Migration skipped: pb_id already exists.");
-        }
+        .map_err(|e| e.to_string())?;
+
+        migrations::seed_legacy_state(&pool)
+            .await
+            .map_err(|e| format!("failed to seed legacy schema state: {}", e))?;
+
+        migrations::run_pending(&pool)
+            .await
+            .map_err(|e| format!("failed to apply migrations: {}", e))?;
 
         // Trigger Creation
         sqlx::query(
-            "CREATE TRIGGER IF NOT EXISTS update_note_timestamp 
+            "CREATE TRIGGER IF NOT EXISTS update_note_timestamp
              AFTER UPDATE ON notes
              BEGIN
                 UPDATE notes SET updated_at = CURRENT_TIMESTAMP WHERE id = old.id;
@@ -97,8 +127,8 @@ impl Database {
         )
         .execute(&pool)
         .await
-        .unwrap();
+        .map_err(|e| e.to_string())?;
 
-        pool
+        Ok(pool)
     }
 }
@@ -1,7 +1,11 @@
 mod commands;
 mod database; // Tell Rust to look for commands.rs
+mod migrations;
+mod repository;
 
 use database::Database;
+use repository::{NoteRepository, SqliteNoteRepo};
+use std::sync::Arc;
 use tauri::Manager;
 
 // We "use" everything from the commands module so the generate_handler can see them
@@ -13,9 +17,12 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .setup(|app| {
             tauri::async_runtime::block_on(async {
-                let db_pool = Database::setup(app.handle()).await;
+                let db_pool = Database::setup(app.handle()).await?;
+                let repo: Arc<dyn NoteRepository> = Arc::new(SqliteNoteRepo::new(db_pool.clone()));
                 app.manage(db_pool);
-            });
+                app.manage(repo);
+                Ok::<(), String>(())
+            })?;
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -27,7 +34,12 @@ pub fn run() {
             update_note_pb_id,
             import_note_from_pb,
             delete_note,
-            delete_note_by_pb_id
+            delete_note_by_pb_id,
+            search_notes,
+            get_note_revisions,
+            restore_note_revision,
+            get_config,
+            save_config
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
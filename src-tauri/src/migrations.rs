@@ -0,0 +1,171 @@
+use sqlx::SqlitePool;
+
+// 1. THE BLUEPRINT
+/// A single forward/backward schema change, applied in order by version.
+pub struct Migration {
+    pub version: i64,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+// The ordered list of migrations compiled into the binary. Append new
+// entries to the end; never edit or reorder an entry that has already
+// shipped.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        up: "ALTER TABLE notes ADD COLUMN pb_id TEXT",
+        down: "ALTER TABLE notes DROP COLUMN pb_id",
+    },
+    Migration {
+        version: 2,
+        up: "CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
+                title, content, content='notes', content_rowid='id'
+            );
+            INSERT INTO notes_fts(rowid, title, content)
+                SELECT id, title, content FROM notes;
+            CREATE TRIGGER IF NOT EXISTS notes_fts_after_insert
+                AFTER INSERT ON notes
+            BEGIN
+                INSERT INTO notes_fts(rowid, title, content)
+                    VALUES (new.id, new.title, new.content);
+            END;
+            CREATE TRIGGER IF NOT EXISTS notes_fts_after_update
+                AFTER UPDATE ON notes
+            BEGIN
+                INSERT INTO notes_fts(notes_fts, rowid, title, content)
+                    VALUES ('delete', old.id, old.title, old.content);
+                INSERT INTO notes_fts(rowid, title, content)
+                    VALUES (new.id, new.title, new.content);
+            END;
+            CREATE TRIGGER IF NOT EXISTS notes_fts_after_delete
+                AFTER DELETE ON notes
+            BEGIN
+                INSERT INTO notes_fts(notes_fts, rowid, title, content)
+                    VALUES ('delete', old.id, old.title, old.content);
+            END;",
+        down: "DROP TRIGGER IF EXISTS notes_fts_after_delete;
+            DROP TRIGGER IF EXISTS notes_fts_after_update;
+            DROP TRIGGER IF EXISTS notes_fts_after_insert;
+            DROP TABLE IF EXISTS notes_fts;",
+    },
+    Migration {
+        version: 3,
+        // Retains the 20 most recent revisions per note; older ones are
+        // pruned in the same trigger that creates the new snapshot.
+        up: "CREATE TABLE IF NOT EXISTS note_revisions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                note_id INTEGER NOT NULL,
+                title TEXT,
+                content TEXT,
+                saved_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE TRIGGER IF NOT EXISTS snapshot_note_revision
+                BEFORE UPDATE ON notes
+            BEGIN
+                INSERT INTO note_revisions (note_id, title, content)
+                    VALUES (old.id, old.title, old.content);
+                DELETE FROM note_revisions
+                    WHERE note_id = old.id
+                    AND id NOT IN (
+                        SELECT id FROM note_revisions
+                        WHERE note_id = old.id
+                        ORDER BY id DESC
+                        LIMIT 20
+                    );
+            END;",
+        down: "DROP TRIGGER IF EXISTS snapshot_note_revision;
+            DROP TABLE IF EXISTS note_revisions;",
+    },
+    Migration {
+        version: 4,
+        // Holds the app's preferences blob, read and written by the
+        // `get_config`/`save_config` commands.
+        up: "CREATE TABLE IF NOT EXISTS config (
+                name TEXT UNIQUE NOT NULL,
+                data TEXT NOT NULL
+            )",
+        down: "DROP TABLE IF EXISTS config",
+    },
+];
+
+// 2. THE BEHAVIOR
+pub async fn current_version(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_migrations")
+        .fetch_one(pool)
+        .await
+}
+
+/// Installs created by the old ad-hoc bootstrap already have `pb_id` on
+/// `notes` with no row in `schema_migrations` to show for it. Seed
+/// version 1 for them so `run_pending` doesn't replay migration 1's
+/// `ALTER TABLE ... ADD COLUMN pb_id` against a column that's already
+/// there.
+pub async fn seed_legacy_state(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let has_pb_id: i64 = sqlx::query_scalar(
+        "SELECT count(*) FROM pragma_table_info('notes') WHERE name = 'pb_id'",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if has_pb_id > 0 {
+        sqlx::query("INSERT OR IGNORE INTO schema_migrations (version) VALUES (1)")
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Applies every migration newer than the current version, each inside
+/// its own transaction so a failing `up` never leaves a half-applied
+/// schema behind.
+pub async fn run_pending(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let current = current_version(pool).await?;
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        println!("Applying migration {}...", migration.version);
+
+        let mut tx = pool.begin().await?;
+        sqlx::raw_sql(migration.up).execute(&mut *tx).await?;
+        sqlx::query("INSERT INTO schema_migrations (version) VALUES ($1)")
+            .bind(migration.version)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        println!("Migration {} applied.", migration.version);
+    }
+
+    Ok(())
+}
+
+/// Runs the `down` SQL for the highest applied version and removes its
+/// row, leaving the schema at the previous version. No-op if nothing has
+/// been applied yet.
+///
+/// Not wired to a command or run during normal startup — this is an
+/// operator escape hatch for manually downgrading a broken migration,
+/// kept here rather than deleted so it's ready when that day comes.
+#[allow(dead_code)]
+pub async fn rollback(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let current = current_version(pool).await?;
+    if current == 0 {
+        return Ok(());
+    }
+
+    let Some(migration) = MIGRATIONS.iter().find(|m| m.version == current) else {
+        return Ok(());
+    };
+
+    let mut tx = pool.begin().await?;
+    sqlx::raw_sql(migration.down).execute(&mut *tx).await?;
+    sqlx::query("DELETE FROM schema_migrations WHERE version = $1")
+        .bind(migration.version)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    println!("Rolled back migration {}.", migration.version);
+    Ok(())
+}
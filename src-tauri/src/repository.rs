@@ -0,0 +1,311 @@
+use crate::commands::{Note, NoteDetail};
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+
+// 1. THE BLUEPRINT
+/// All note persistence the app needs, behind a trait so the command
+/// layer can be tested against an in-memory implementation instead of a
+/// real database.
+#[async_trait]
+pub trait NoteRepository: Send + Sync {
+    async fn create(&self, title: String, content: String) -> Result<i64, String>;
+    async fn list(&self) -> Result<Vec<Note>, String>;
+    async fn get_content(&self, id: i64) -> Result<Option<NoteDetail>, String>;
+    async fn update(&self, id: i64, title: String, content: String) -> Result<(), String>;
+    async fn set_pb_id(&self, id: i64, pb_id: String) -> Result<(), String>;
+    async fn import(
+        &self,
+        pb_id: String,
+        title: String,
+        content: String,
+        updated_at: String,
+    ) -> Result<i64, String>;
+    async fn delete(&self, id: i64) -> Result<(), String>;
+    async fn delete_by_pb_id(&self, pb_id: String) -> Result<(), String>;
+}
+
+// 2. THE BEHAVIOR
+pub struct SqliteNoteRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteNoteRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl NoteRepository for SqliteNoteRepo {
+    async fn create(&self, title: String, content: String) -> Result<i64, String> {
+        let result = sqlx::query("INSERT INTO notes (title, content) VALUES ($1, $2)")
+            .bind(title)
+            .bind(content)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn list(&self) -> Result<Vec<Note>, String> {
+        sqlx::query_as::<_, Note>(
+            "SELECT id, title, updated_at, pb_id FROM notes ORDER BY updated_at DESC, id DESC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| e.to_string())
+    }
+
+    async fn get_content(&self, id: i64) -> Result<Option<NoteDetail>, String> {
+        sqlx::query_as::<_, NoteDetail>(
+            "SELECT id, title, content, updated_at, pb_id FROM notes WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| e.to_string())
+    }
+
+    async fn update(&self, id: i64, title: String, content: String) -> Result<(), String> {
+        sqlx::query("UPDATE notes SET title = $1, content = $2 WHERE id = $3")
+            .bind(title)
+            .bind(content)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    async fn set_pb_id(&self, id: i64, pb_id: String) -> Result<(), String> {
+        sqlx::query("UPDATE notes SET pb_id = $1 WHERE id = $2")
+            .bind(pb_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    async fn import(
+        &self,
+        pb_id: String,
+        title: String,
+        content: String,
+        updated_at: String,
+    ) -> Result<i64, String> {
+        let result = sqlx::query(
+            "INSERT INTO notes (title, content, updated_at, pb_id) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(title)
+        .bind(content)
+        .bind(updated_at)
+        .bind(pb_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), String> {
+        sqlx::query("DELETE FROM notes WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    async fn delete_by_pb_id(&self, pb_id: String) -> Result<(), String> {
+        sqlx::query("DELETE FROM notes WHERE pb_id = $1")
+            .bind(pb_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct StoredNote {
+        title: String,
+        content: String,
+        updated_at: String,
+        pb_id: Option<String>,
+    }
+
+    /// In-memory `NoteRepository`, so the command layer's note logic can
+    /// be exercised without a real database.
+    #[derive(Default)]
+    struct InMemoryNoteRepo {
+        notes: Mutex<Vec<(i64, StoredNote)>>,
+        next_id: Mutex<i64>,
+    }
+
+    #[async_trait]
+    impl NoteRepository for InMemoryNoteRepo {
+        async fn create(&self, title: String, content: String) -> Result<i64, String> {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            let id = *next_id;
+
+            self.notes.lock().unwrap().push((
+                id,
+                StoredNote {
+                    title,
+                    content,
+                    updated_at: "now".to_string(),
+                    pb_id: None,
+                },
+            ));
+
+            Ok(id)
+        }
+
+        async fn list(&self) -> Result<Vec<Note>, String> {
+            Ok(self
+                .notes
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(id, note)| Note {
+                    id: *id,
+                    title: note.title.clone(),
+                    updated_at: note.updated_at.clone(),
+                    pb_id: note.pb_id.clone(),
+                })
+                .collect())
+        }
+
+        async fn get_content(&self, id: i64) -> Result<Option<NoteDetail>, String> {
+            Ok(self
+                .notes
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(note_id, _)| *note_id == id)
+                .map(|(id, note)| NoteDetail {
+                    id: *id,
+                    title: note.title.clone(),
+                    content: Some(note.content.clone()),
+                    updated_at: note.updated_at.clone(),
+                    pb_id: note.pb_id.clone(),
+                }))
+        }
+
+        async fn update(&self, id: i64, title: String, content: String) -> Result<(), String> {
+            if let Some((_, note)) = self
+                .notes
+                .lock()
+                .unwrap()
+                .iter_mut()
+                .find(|(note_id, _)| *note_id == id)
+            {
+                note.title = title;
+                note.content = content;
+            }
+            Ok(())
+        }
+
+        async fn set_pb_id(&self, id: i64, pb_id: String) -> Result<(), String> {
+            if let Some((_, note)) = self
+                .notes
+                .lock()
+                .unwrap()
+                .iter_mut()
+                .find(|(note_id, _)| *note_id == id)
+            {
+                note.pb_id = Some(pb_id);
+            }
+            Ok(())
+        }
+
+        async fn import(
+            &self,
+            pb_id: String,
+            title: String,
+            content: String,
+            updated_at: String,
+        ) -> Result<i64, String> {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            let id = *next_id;
+
+            self.notes.lock().unwrap().push((
+                id,
+                StoredNote {
+                    title,
+                    content,
+                    updated_at,
+                    pb_id: Some(pb_id),
+                },
+            ));
+
+            Ok(id)
+        }
+
+        async fn delete(&self, id: i64) -> Result<(), String> {
+            self.notes.lock().unwrap().retain(|(note_id, _)| *note_id != id);
+            Ok(())
+        }
+
+        async fn delete_by_pb_id(&self, pb_id: String) -> Result<(), String> {
+            self.notes
+                .lock()
+                .unwrap()
+                .retain(|(_, note)| note.pb_id.as_deref() != Some(pb_id.as_str()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn create_then_list_returns_the_new_note() {
+        let repo: Arc<dyn NoteRepository> = Arc::new(InMemoryNoteRepo::default());
+
+        let id = repo
+            .create("Title".to_string(), "Body".to_string())
+            .await
+            .unwrap();
+
+        let notes = repo.list().await.unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].id, id);
+        assert_eq!(notes[0].title, "Title");
+    }
+
+    #[tokio::test]
+    async fn update_changes_title_and_content() {
+        let repo: Arc<dyn NoteRepository> = Arc::new(InMemoryNoteRepo::default());
+        let id = repo
+            .create("Old".to_string(), "Old body".to_string())
+            .await
+            .unwrap();
+
+        repo.update(id, "New".to_string(), "New body".to_string())
+            .await
+            .unwrap();
+
+        let detail = repo.get_content(id).await.unwrap().unwrap();
+        assert_eq!(detail.title, "New");
+        assert_eq!(detail.content, Some("New body".to_string()));
+    }
+
+    #[tokio::test]
+    async fn delete_removes_the_note() {
+        let repo: Arc<dyn NoteRepository> = Arc::new(InMemoryNoteRepo::default());
+        let id = repo
+            .create("Title".to_string(), "Body".to_string())
+            .await
+            .unwrap();
+
+        repo.delete(id).await.unwrap();
+
+        assert!(repo.get_content(id).await.unwrap().is_none());
+    }
+}